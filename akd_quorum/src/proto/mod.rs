@@ -205,6 +205,82 @@ where
     }
 }
 
+// Fold a node's label together with its hash into a single digest, so the
+// running accumulator in `try_from_verified` depends on which label a hash
+// is attached to, not just the multiset of hashes present in the proof.
+fn node_digest<H>(node: &akd::node_state::Node<H>) -> H::Digest
+where
+    H: winter_crypto::Hasher,
+{
+    let label_digest = H::hash(format!("{}:{}", node.label.len, node.label.val).as_bytes());
+    H::merge(&[label_digest, node.hash.clone()])
+}
+
+impl<H> crate::node::messages::inter_node::VerifyRequest<H>
+where
+    H: winter_crypto::Hasher,
+{
+    /// Decode a [`inter_node::VerifyRequest`] the same way as the `TryFrom`
+    /// impl above, but fold each `inserted`/`unchanged` node's label and
+    /// hash into a running digest as it comes off the wire instead of
+    /// materializing the full `AppendOnlyProof` first and hashing it
+    /// afterwards.
+    ///
+    /// The `unchanged` nodes are folded first: on their own, they must hash
+    /// to `previous_hash`, so a corrupt or malicious proof is rejected
+    /// before a single byte of the (potentially much larger) `inserted`
+    /// list is decoded. Folding then continues over `inserted`, and the
+    /// result must hash to `new_hash`. Either mismatch aborts decoding
+    /// immediately with a `CommunicationError::Serialization`.
+    pub fn try_from_verified(
+        input: &inter_node::VerifyRequest,
+    ) -> Result<Self, crate::comms::CommunicationError> {
+        require!(input, has_epoch);
+        require!(input, has_new_hash);
+        require!(input, has_previous_hash);
+        require!(input, has_proof);
+
+        let previous_hash: H::Digest = hash_from_bytes!(input.get_previous_hash());
+        let new_hash: H::Digest = hash_from_bytes!(input.get_new_hash());
+        let proof = input.get_proof();
+
+        let mut running_hash = H::hash(&[]);
+        let mut unchanged = Vec::with_capacity(proof.get_unchanged().len());
+        for item in proof.get_unchanged() {
+            let node: akd::node_state::Node<H> = item.try_into()?;
+            running_hash = H::merge(&[running_hash, node_digest(&node)]);
+            unchanged.push(node);
+        }
+        if running_hash != previous_hash {
+            return Err(crate::comms::CommunicationError::Serialization(
+                "Accumulated hash of unchanged nodes did not match previous_hash".to_string(),
+            ));
+        }
+
+        let mut inserted = Vec::with_capacity(proof.get_inserted().len());
+        for item in proof.get_inserted() {
+            let node: akd::node_state::Node<H> = item.try_into()?;
+            running_hash = H::merge(&[running_hash, node_digest(&node)]);
+            inserted.push(node);
+        }
+        if running_hash != new_hash {
+            return Err(crate::comms::CommunicationError::Serialization(
+                "Accumulated hash of inserted nodes did not match new_hash".to_string(),
+            ));
+        }
+
+        Ok(crate::node::messages::inter_node::VerifyRequest::<H> {
+            epoch: input.get_epoch(),
+            new_hash,
+            previous_hash,
+            append_only_proof: akd::proof_structs::AppendOnlyProof {
+                inserted,
+                unchanged_nodes: unchanged,
+            },
+        })
+    }
+}
+
 // ==============================================================
 // Verify Response
 // ==============================================================
@@ -256,3 +332,60 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct TestDigest(Vec<u8>);
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct TestHasher;
+
+    impl winter_crypto::Hasher for TestHasher {
+        type Digest = TestDigest;
+
+        fn hash(bytes: &[u8]) -> Self::Digest {
+            TestDigest(bytes.to_vec())
+        }
+
+        fn merge(values: &[Self::Digest; 2]) -> Self::Digest {
+            let mut out = values[0].0.clone();
+            out.extend_from_slice(&values[1].0);
+            TestDigest(out)
+        }
+
+        fn merge_with_int(seed: Self::Digest, value: u64) -> Self::Digest {
+            let mut out = seed.0;
+            out.extend_from_slice(&value.to_be_bytes());
+            TestDigest(out)
+        }
+    }
+
+    fn node(len: u32, val: u64, hash: &[u8]) -> akd::node_state::Node<TestHasher> {
+        akd::node_state::Node {
+            label: akd::node_state::NodeLabel { len, val },
+            hash: TestDigest(hash.to_vec()),
+        }
+    }
+
+    // Regression test for a bug where the running digest folded in only
+    // `node.hash`, making it invariant to which label a hash was attached
+    // to, so labels could be permuted across the proof undetected.
+    #[test]
+    fn node_digest_depends_on_the_label_not_just_the_hash() {
+        let a = node(1, 2, b"same-hash");
+        let b = node(3, 4, b"same-hash");
+
+        assert_ne!(node_digest(&a), node_digest(&b));
+    }
+
+    #[test]
+    fn node_digest_depends_on_the_hash_too() {
+        let a = node(1, 2, b"hash-a");
+        let b = node(1, 2, b"hash-b");
+
+        assert_ne!(node_digest(&a), node_digest(&b));
+    }
+}
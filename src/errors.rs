@@ -0,0 +1,30 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+use std::fmt;
+
+/// Errors that can occur while interacting with a [`crate::storage::Storage`] backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageError {
+    /// A `get` could not return a value for the given position: no value is
+    /// stored there, or the backend failed to read or decode it.
+    GetError,
+    /// A write (`set` or `commit`) failed to persist, or the backend could
+    /// not be opened.
+    SetError,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::GetError => write!(f, "failed to retrieve value from storage"),
+            StorageError::SetError => write!(f, "failed to persist value to storage"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
@@ -0,0 +1,228 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::errors::StorageError;
+use crate::storage::Storage;
+use lru_cache::LruCache;
+
+/// Default bound on the number of entries held in the read cache.
+const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+/// Number of buffered writes after which `set` eagerly flushes to the inner
+/// storage, bounding how much unflushed state can accumulate.
+const DEFAULT_WRITE_BUFFER_THRESHOLD: usize = 1_000;
+
+// ===== Caching storage decorator ==== //
+
+/// A [`Storage`] wrapper that adds a bounded read cache and a batched write
+/// buffer in front of an inner backend `S`.
+///
+/// `get` is served from the cache, falling through to `S::get` on a miss.
+/// `set` lands in a write buffer that is only drained down to `S::commit`
+/// as a batch, either explicitly via [`CachingStorage::flush`] or once the
+/// buffer grows past its configured threshold.
+#[derive(Debug)]
+pub struct CachingStorage<S: Storage> {
+    inner: S,
+    cache: Arc<Mutex<LruCache<String, String>>>,
+    write_buffer: Arc<Mutex<HashMap<String, String>>>,
+    write_buffer_threshold: usize,
+}
+
+impl<S: Storage> CachingStorage<S> {
+    /// Wrap `inner` with a read cache sized to `DEFAULT_CACHE_CAPACITY` entries.
+    pub fn new(inner: S) -> Self {
+        Self::new_with_capacity(inner, DEFAULT_CACHE_CAPACITY, DEFAULT_WRITE_BUFFER_THRESHOLD)
+    }
+
+    /// Wrap `inner` with a read cache bounded to `cache_capacity` entries and
+    /// a write buffer that auto-flushes once it holds more than
+    /// `write_buffer_threshold` entries.
+    pub fn new_with_capacity(
+        inner: S,
+        cache_capacity: usize,
+        write_buffer_threshold: usize,
+    ) -> Self {
+        CachingStorage {
+            inner,
+            cache: Arc::new(Mutex::new(LruCache::new(cache_capacity))),
+            write_buffer: Arc::new(Mutex::new(HashMap::new())),
+            write_buffer_threshold,
+        }
+    }
+
+    /// Flush every buffered write down to the inner storage as a single
+    /// `commit`, so they land atomically instead of one `set` at a time.
+    pub fn flush(&self) -> Result<(), StorageError> {
+        let mut write_buffer = self.write_buffer.lock().unwrap();
+        if write_buffer.is_empty() {
+            return Ok(());
+        }
+
+        let writes: Vec<(String, String)> = write_buffer.drain().collect();
+        self.inner.commit(writes)
+    }
+}
+
+impl<S: Storage> Storage for CachingStorage<S> {
+    fn set(&self, pos: String, value: String) -> Result<(), StorageError> {
+        self.cache.lock().unwrap().insert(pos.clone(), value.clone());
+
+        let should_flush = {
+            let mut write_buffer = self.write_buffer.lock().unwrap();
+            write_buffer.insert(pos, value);
+            write_buffer.len() > self.write_buffer_threshold
+        };
+
+        if should_flush {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, pos: String) -> Result<String, StorageError> {
+        if let Some(value) = self.cache.lock().unwrap().get_mut(&pos) {
+            return Ok(value.clone());
+        }
+
+        // The write buffer may hold a value that hasn't been flushed to
+        // `inner` yet, so it has to be checked before falling through.
+        if let Some(value) = self.write_buffer.lock().unwrap().get(&pos) {
+            let value = value.clone();
+            self.cache.lock().unwrap().insert(pos, value.clone());
+            return Ok(value);
+        }
+
+        let value = self.inner.get(pos.clone())?;
+        self.cache.lock().unwrap().insert(pos, value.clone());
+        Ok(value)
+    }
+
+    fn commit(&self, writes: Vec<(String, String)>) -> Result<(), StorageError> {
+        {
+            let mut cache = self.cache.lock().unwrap();
+            for (pos, value) in &writes {
+                cache.insert(pos.clone(), value.clone());
+            }
+        }
+
+        let should_flush = {
+            let mut write_buffer = self.write_buffer.lock().unwrap();
+            for (pos, value) in writes {
+                write_buffer.insert(pos, value);
+            }
+            write_buffer.len() > self.write_buffer_threshold
+        };
+
+        if should_flush {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: Storage> Clone for CachingStorage<S> {
+    fn clone(&self) -> Self {
+        CachingStorage {
+            inner: self.inner.clone(),
+            cache: self.cache.clone(),
+            write_buffer: self.write_buffer.clone(),
+            write_buffer_threshold: self.write_buffer_threshold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, Default)]
+    struct MockStorage {
+        values: Arc<Mutex<HashMap<String, String>>>,
+        commit_calls: Arc<Mutex<usize>>,
+    }
+
+    impl Storage for MockStorage {
+        fn set(&self, pos: String, value: String) -> Result<(), StorageError> {
+            self.values.lock().unwrap().insert(pos, value);
+            Ok(())
+        }
+
+        fn get(&self, pos: String) -> Result<String, StorageError> {
+            self.values
+                .lock()
+                .unwrap()
+                .get(&pos)
+                .cloned()
+                .ok_or(StorageError::GetError)
+        }
+
+        fn commit(&self, writes: Vec<(String, String)>) -> Result<(), StorageError> {
+            *self.commit_calls.lock().unwrap() += 1;
+            let mut values = self.values.lock().unwrap();
+            for (pos, value) in writes {
+                values.insert(pos, value);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn get_falls_through_to_inner_on_cache_miss() {
+        let inner = MockStorage::default();
+        inner.set(String::from("k"), String::from("v")).unwrap();
+
+        let cached = CachingStorage::new(inner);
+        assert_eq!(cached.get(String::from("k")).unwrap(), "v");
+    }
+
+    #[test]
+    fn set_is_buffered_until_flush() {
+        let inner = MockStorage::default();
+        let cached = CachingStorage::new_with_capacity(inner.clone(), 10, 10);
+
+        cached.set(String::from("k"), String::from("v")).unwrap();
+        assert!(inner.get(String::from("k")).is_err());
+        assert_eq!(cached.get(String::from("k")).unwrap(), "v");
+
+        cached.flush().unwrap();
+        assert_eq!(inner.get(String::from("k")).unwrap(), "v");
+    }
+
+    #[test]
+    fn set_auto_flushes_past_the_write_buffer_threshold() {
+        let inner = MockStorage::default();
+        let cached = CachingStorage::new_with_capacity(inner.clone(), 10, 1);
+
+        cached.set(String::from("a"), String::from("1")).unwrap();
+        cached.set(String::from("b"), String::from("2")).unwrap();
+
+        // The buffer grew past its threshold of 1, so it must have
+        // auto-flushed down to the inner store.
+        assert_eq!(inner.get(String::from("a")).unwrap(), "1");
+        assert_eq!(inner.get(String::from("b")).unwrap(), "2");
+    }
+
+    #[test]
+    fn flush_lands_the_whole_buffer_in_a_single_commit_call() {
+        let inner = MockStorage::default();
+        let cached = CachingStorage::new_with_capacity(inner.clone(), 10, 10);
+
+        cached.set(String::from("a"), String::from("1")).unwrap();
+        cached.set(String::from("b"), String::from("2")).unwrap();
+        cached.flush().unwrap();
+
+        assert_eq!(*inner.commit_calls.lock().unwrap(), 1);
+        assert_eq!(inner.get(String::from("a")).unwrap(), "1");
+        assert_eq!(inner.get(String::from("b")).unwrap(), "2");
+    }
+}
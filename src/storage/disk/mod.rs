@@ -0,0 +1,131 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::errors::StorageError;
+use crate::storage::caching::CachingStorage;
+use crate::storage::Storage;
+use rocksdb::{WriteBatch, DB};
+
+// ===== Disk-backed database ==== //
+
+/// A disk-backed [`Storage`] implementation built on RocksDB.
+///
+/// Unlike [`crate::storage::memory::InMemoryDatabase`], the state written
+/// here outlives the process: `open` on an existing path picks up whatever
+/// was already durably written.
+#[derive(Debug)]
+pub struct DiskDatabase {
+    db: Arc<DB>,
+}
+
+impl DiskDatabase {
+    /// Open (or create) the database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<DiskDatabase, StorageError> {
+        let db = DB::open_default(path).map_err(|_| StorageError::SetError)?;
+        Ok(DiskDatabase { db: Arc::new(db) })
+    }
+
+    /// Open (or create) the database at `path`, wrapped in a
+    /// [`CachingStorage`] so reads are served from a bounded in-memory cache
+    /// instead of hitting disk on every lookup.
+    pub fn open_cached<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<CachingStorage<DiskDatabase>, StorageError> {
+        Ok(CachingStorage::new(Self::open(path)?))
+    }
+
+    /// Force all buffered writes out to disk.
+    pub fn flush(&self) -> Result<(), StorageError> {
+        self.db.flush().map_err(|_| StorageError::SetError)
+    }
+
+    /// Compact the full key range, reclaiming space left by overwritten or
+    /// deleted entries.
+    pub fn compact(&self) {
+        self.db.compact_range::<&[u8], &[u8]>(None, None);
+    }
+}
+
+impl Storage for DiskDatabase {
+    fn set(&self, pos: String, value: String) -> Result<(), StorageError> {
+        self.db
+            .put(pos.as_bytes(), value.as_bytes())
+            .map_err(|_| StorageError::SetError)
+    }
+
+    fn get(&self, pos: String) -> Result<String, StorageError> {
+        let bytes = self
+            .db
+            .get(pos.as_bytes())
+            .map_err(|_| StorageError::GetError)?
+            .ok_or(StorageError::GetError)?;
+        String::from_utf8(bytes).map_err(|_| StorageError::GetError)
+    }
+
+    fn commit(&self, writes: Vec<(String, String)>) -> Result<(), StorageError> {
+        let mut batch = WriteBatch::default();
+        for (pos, value) in writes {
+            batch.put(pos.as_bytes(), value.as_bytes());
+        }
+        self.db.write(batch).map_err(|_| StorageError::SetError)
+    }
+}
+
+impl Clone for DiskDatabase {
+    fn clone(&self) -> DiskDatabase {
+        DiskDatabase { db: self.db.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("akd_disk_database_test_{}_{}", name, std::process::id()));
+        dir
+    }
+
+    #[test]
+    fn set_value_survives_reopening_the_database() {
+        let path = temp_path("reopen");
+        let _ = std::fs::remove_dir_all(&path);
+
+        {
+            let db = DiskDatabase::open(&path).unwrap();
+            db.set(String::from("k"), String::from("v")).unwrap();
+            db.flush().unwrap();
+        }
+
+        let reopened = DiskDatabase::open(&path).unwrap();
+        assert_eq!(reopened.get(String::from("k")).unwrap(), "v");
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn commit_applies_every_write_in_the_batch() {
+        let path = temp_path("commit");
+        let _ = std::fs::remove_dir_all(&path);
+
+        let db = DiskDatabase::open(&path).unwrap();
+        db.commit(vec![
+            (String::from("a"), String::from("1")),
+            (String::from("b"), String::from("2")),
+        ])
+        .unwrap();
+
+        assert_eq!(db.get(String::from("a")).unwrap(), "1");
+        assert_eq!(db.get(String::from("b")).unwrap(), "2");
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -9,8 +9,8 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use crate::errors::StorageError;
 use crate::storage::Storage;
-use lazy_static::lazy_static;
 use evmap::{ReadHandle, WriteHandle};
+use lru_cache::LruCache;
 
 
 // ===== Basic In-Memory database ==== //
@@ -46,6 +46,17 @@ impl Storage for InMemoryDatabase {
         }
         Result::Err(StorageError::GetError)
     }
+
+    fn commit(&self, writes: Vec<(String, String)>) -> Result<(), StorageError> {
+        let mut hashmap = self.write_handle.lock().unwrap();
+        for (pos, value) in writes {
+            // evmap supports multi-values, so we need to clear the value if it's present and then set the new value
+            hashmap.clear(pos.clone());
+            hashmap.insert(pos, value);
+        }
+        hashmap.refresh();
+        Ok(())
+    }
 }
 
 impl Clone for InMemoryDatabase {
@@ -56,42 +67,70 @@ impl Clone for InMemoryDatabase {
 
 // ===== In-Memory database w/caching ==== //
 
-lazy_static! {
-    static ref CACHE_DB: Mutex<HashMap<String, String>> = {
-        let m = HashMap::new();
-        Mutex::new(m)
-    };
-    static ref CACHE_CACHE: Mutex<HashMap<String, String>> = {
-        let m = HashMap::new();
-        Mutex::new(m)
-    };
-    static ref CACHE_STATS: Mutex<HashMap<String, usize>> = {
-        let m = HashMap::new();
-        Mutex::new(m)
-    };
-}
+/// The default number of entries the cache will hold before it starts
+/// evicting the least-recently-used ones.
+const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+/// A cached value, along with whether it has been written since it was
+/// last flushed to the backing store.
+type CacheEntry = (String, bool);
 
 #[derive(Debug)]
-pub struct InMemoryDbWithCache(());
+pub struct InMemoryDbWithCache {
+    db: Arc<Mutex<HashMap<String, String>>>,
+    cache: Arc<Mutex<LruCache<String, CacheEntry>>>,
+    stats: Arc<Mutex<HashMap<String, usize>>>,
+    capacity: usize,
+}
 
 impl InMemoryDbWithCache {
     pub fn new() -> InMemoryDbWithCache {
-        InMemoryDbWithCache(())
+        Self::new_with_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Construct a database with a cache bounded to `capacity` entries.
+    pub fn new_with_capacity(capacity: usize) -> InMemoryDbWithCache {
+        InMemoryDbWithCache {
+            db: Arc::new(Mutex::new(HashMap::new())),
+            cache: Arc::new(Mutex::new(LruCache::new(capacity))),
+            stats: Arc::new(Mutex::new(HashMap::new())),
+            capacity,
+        }
+    }
+
+    fn bump_stat(&self, name: &str) {
+        let mut stats = self.stats.lock().unwrap();
+        let counter = stats.entry(String::from(name)).or_insert(0);
+        *counter += 1;
+    }
+
+    // Evict the least-recently-used entry (if the cache is at capacity and
+    // `key` isn't already present, so the upcoming insert would grow it
+    // past `capacity`), writing it back to the backing store if it's dirty.
+    fn evict_if_full(&self, cache: &mut LruCache<String, CacheEntry>, key: &str) {
+        if cache.contains_key(key) || cache.len() < self.capacity {
+            return;
+        }
+        if let Some((evicted_key, (evicted_value, dirty))) = cache.remove_lru() {
+            if dirty {
+                self.db.lock().unwrap().insert(evicted_key, evicted_value);
+            }
+        }
     }
 
     pub fn clear_stats(&self) {
         // Flush cache to db
 
-        let mut cache = CACHE_CACHE.lock().unwrap();
+        let mut cache = self.cache.lock().unwrap();
 
-        let mut db = CACHE_DB.lock().unwrap();
-        for (key, val) in cache.iter() {
+        let mut db = self.db.lock().unwrap();
+        for (key, (val, _dirty)) in cache.iter() {
             db.insert(key.clone(), val.clone());
         }
 
         cache.clear();
 
-        let mut stats = CACHE_STATS.lock().unwrap();
+        let mut stats = self.stats.lock().unwrap();
         stats.clear();
     }
 
@@ -99,11 +138,15 @@ impl InMemoryDbWithCache {
         println!("Statistics collected:");
         println!("---------------------");
 
-        let stats = CACHE_STATS.lock().unwrap();
+        let stats = self.stats.lock().unwrap();
         for (key, val) in stats.iter() {
             println!("{}: {}", key, val);
         }
 
+        let cache = self.cache.lock().unwrap();
+        println!("cache_capacity: {}", self.capacity);
+        println!("cache_len: {}", cache.len());
+
         println!("---------------------");
     }
 
@@ -111,11 +154,11 @@ impl InMemoryDbWithCache {
         println!("Cache distribution of length of entries (in bytes):");
         println!("---------------------");
 
-        let cache = CACHE_CACHE.lock().unwrap();
+        let cache = self.cache.lock().unwrap();
 
         let mut distribution: HashMap<usize, usize> = HashMap::new();
 
-        for (_, val) in cache.iter() {
+        for (_, (val, _dirty)) in cache.iter() {
             let len = val.len();
 
             let counter = distribution.entry(len).or_insert(0);
@@ -137,44 +180,115 @@ impl InMemoryDbWithCache {
 
 impl Storage for InMemoryDbWithCache {
     fn set(&self, pos: String, value: String) -> Result<(), StorageError> {
-        let mut stats = CACHE_STATS.lock().unwrap();
-        let calls_to_cache_set = stats.entry(String::from("calls_to_cache_set")).or_insert(0);
-        *calls_to_cache_set += 1;
+        self.bump_stat("calls_to_cache_set");
 
-        let mut cache = CACHE_CACHE.lock().unwrap();
-        cache.insert(pos.clone(), value.clone());
+        let mut cache = self.cache.lock().unwrap();
+        self.evict_if_full(&mut cache, &pos);
+        cache.insert(pos, (value, true));
 
         Ok(())
     }
 
     fn get(&self, pos: String) -> Result<String, StorageError> {
-        let mut stats = CACHE_STATS.lock().unwrap();
-
-        let cache = &mut CACHE_CACHE.lock().unwrap();
-        let calls_to_cache_get = stats.entry(String::from("calls_to_cache_get")).or_insert(0);
-        *calls_to_cache_get += 1;
-
-        match cache.get(&pos) {
-            Some(value) => Ok(value.clone()),
-            None => {
-                let calls_to_db_get = stats.entry(String::from("calls_to_db_get")).or_insert(0);
-                *calls_to_db_get += 1;
-
-                let db = CACHE_DB.lock().unwrap();
-                let value = db
-                    .get(&pos)
-                    .map(|v| v.clone())
-                    .ok_or(StorageError::GetError)?;
-
-                cache.insert(pos, value.clone());
-                Ok(value)
-            }
+        self.bump_stat("calls_to_cache_get");
+
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((value, _dirty)) = cache.get_mut(&pos) {
+            return Ok(value.clone());
+        }
+        drop(cache);
+
+        self.bump_stat("calls_to_db_get");
+
+        let value = {
+            let db = self.db.lock().unwrap();
+            db.get(&pos).map(|v| v.clone()).ok_or(StorageError::GetError)?
+        };
+
+        let mut cache = self.cache.lock().unwrap();
+        self.evict_if_full(&mut cache, &pos);
+        cache.insert(pos, (value.clone(), false));
+        Ok(value)
+    }
+
+    fn commit(&self, writes: Vec<(String, String)>) -> Result<(), StorageError> {
+        self.bump_stat("calls_to_cache_set");
+
+        let mut cache = self.cache.lock().unwrap();
+        for (pos, value) in writes {
+            self.evict_if_full(&mut cache, &pos);
+            cache.insert(pos, (value, true));
         }
+
+        Ok(())
     }
 }
 
 impl Clone for InMemoryDbWithCache {
     fn clone(&self) -> InMemoryDbWithCache {
-        InMemoryDbWithCache::new()
+        InMemoryDbWithCache {
+            db: self.db.clone(),
+            cache: self.cache.clone(),
+            stats: self.stats.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_the_lru_entry_and_writes_it_back_if_dirty() {
+        let store = InMemoryDbWithCache::new_with_capacity(2);
+        store.set(String::from("a"), String::from("1")).unwrap();
+        store.set(String::from("b"), String::from("2")).unwrap();
+
+        // Touch "a" so "b" becomes the least-recently-used entry.
+        assert_eq!(store.get(String::from("a")).unwrap(), "1");
+        store.set(String::from("c"), String::from("3")).unwrap();
+
+        // "b" was evicted, but it was dirty, so it must have been written
+        // back to the backing store before being dropped.
+        assert_eq!(store.get(String::from("b")).unwrap(), "2");
+    }
+
+    #[test]
+    fn instances_do_not_share_cache_or_backing_store() {
+        let a = InMemoryDbWithCache::new();
+        let b = InMemoryDbWithCache::new();
+
+        a.set(String::from("k"), String::from("v")).unwrap();
+
+        assert_eq!(a.get(String::from("k")).unwrap(), "v");
+        assert!(b.get(String::from("k")).is_err());
+    }
+
+    #[test]
+    fn in_memory_database_commit_applies_every_write_in_the_batch() {
+        let db = InMemoryDatabase::new();
+        db.commit(vec![
+            (String::from("x"), String::from("1")),
+            (String::from("y"), String::from("2")),
+        ])
+        .unwrap();
+
+        assert_eq!(db.get(String::from("x")).unwrap(), "1");
+        assert_eq!(db.get(String::from("y")).unwrap(), "2");
+    }
+
+    #[test]
+    fn in_memory_db_with_cache_commit_applies_every_write_in_the_batch() {
+        let store = InMemoryDbWithCache::new();
+        store
+            .commit(vec![
+                (String::from("x"), String::from("1")),
+                (String::from("y"), String::from("2")),
+            ])
+            .unwrap();
+
+        assert_eq!(store.get(String::from("x")).unwrap(), "1");
+        assert_eq!(store.get(String::from("y")).unwrap(), "2");
     }
 }
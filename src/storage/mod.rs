@@ -0,0 +1,50 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree and the Apache
+// License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+// of this source tree.
+
+use crate::errors::StorageError;
+
+pub mod caching;
+pub mod disk;
+pub mod memory;
+
+/// An abstract key-value storage backend for the AKD's node state.
+///
+/// Implementors store opaque blobs addressed by a `String` position, so the
+/// directory can thread its node labels, hashes, and proofs through whatever
+/// backing store (in-memory, on-disk, remote, ...) is configured without
+/// changing any directory logic.
+pub trait Storage: Clone {
+    /// Set a value at a position in the storage.
+    fn set(&self, pos: String, value: String) -> Result<(), StorageError>;
+
+    /// Retrieve a value previously stored at a position.
+    fn get(&self, pos: String) -> Result<String, StorageError>;
+
+    /// Apply a batch of writes as a single, all-or-nothing unit.
+    ///
+    /// This lets callers such as an AKD epoch publish — which touches many
+    /// node labels and an append-only proof at once — commit all of them
+    /// atomically instead of one `set` at a time. The default
+    /// implementation simply applies each write independently; backends
+    /// that can commit a batch atomically, or more cheaply than one `set`
+    /// at a time, should override it.
+    fn commit(&self, writes: Vec<(String, String)>) -> Result<(), StorageError> {
+        for (pos, value) in writes {
+            self.set(pos, value)?;
+        }
+        Ok(())
+    }
+
+    /// Retrieve the values stored at a batch of positions, in order.
+    ///
+    /// The default implementation simply calls `get` for each position;
+    /// backends that can answer a batch more cheaply than one `get` at a
+    /// time should override it.
+    fn get_batch(&self, positions: Vec<String>) -> Result<Vec<String>, StorageError> {
+        positions.into_iter().map(|pos| self.get(pos)).collect()
+    }
+}